@@ -0,0 +1,279 @@
+//! A standalone byte-level BPE tokenizer, for use without a BlingFire model.
+//!
+//! The tokenizer reads the HuggingFace `vocab.json` and `merges.txt` files used
+//! by GPT-2 style models and follows the same steps: the text is split into
+//! words, each word's UTF-8 bytes are mapped to printable characters, and the
+//! merges are applied in rank order. The implementation is straightforward
+//! rather than fast, so prefer a BlingFire model where one is available.
+
+use crate::errors::{Error, Result};
+use std::collections::HashMap;
+
+/// A byte-level BPE tokenizer built from a vocabulary and a list of merges.
+#[derive(Debug, Clone)]
+pub struct BpeTokenizer {
+    vocab: HashMap<String, i32>,
+    /// Rank of each merge, indexed by its left then its right symbol so lookups
+    /// do not allocate.
+    merge_ranks: HashMap<String, HashMap<String, usize>>,
+    byte_encoder: [char; 256],
+}
+
+impl BpeTokenizer {
+    /// Creates a tokenizer from the contents of a `vocab.json` file (a JSON
+    /// object mapping tokens to IDs) and a `merges.txt` file (one
+    /// space-separated pair per line, highest priority first, with an optional
+    /// `#version` header).
+    ///
+    /// ## Errors
+    ///
+    /// Returns `Error::InvalidBpeVocab` if `vocab` is not a JSON object of
+    /// integer IDs, and `Error::InvalidBpeMerges` if a line of `merges` is not
+    /// a pair.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), blingfire::Error> {
+    ///     use blingfire::bpe::BpeTokenizer;
+    ///
+    ///     let vocab = r#"{"a": 0, "b": 1, "ab": 2, "Ġ": 3, "Ġab": 4}"#;
+    ///     let merges = "#version: 0.2\na b\nĠ ab\n";
+    ///     let tokenizer = BpeTokenizer::from_vocab_and_merges(vocab, merges)?;
+    ///     assert_eq!(tokenizer.encode("ab ab"), vec![2, 4]);
+    ///     # Ok(())
+    /// # }
+    /// ```
+    pub fn from_vocab_and_merges(vocab: &str, merges: &str) -> Result<BpeTokenizer> {
+        let vocab: HashMap<String, i32> =
+            serde_json::from_str(vocab).map_err(|error| Error::InvalidBpeVocab {
+                reason: error.to_string(),
+            })?;
+
+        let mut merge_ranks: HashMap<String, HashMap<String, usize>> = HashMap::new();
+        let mut rank = 0;
+        for (index, line) in merges.lines().enumerate() {
+            if (index == 0 && line.starts_with("#version")) || line.trim().is_empty() {
+                continue;
+            }
+            let mut symbols = line.split_whitespace();
+            match (symbols.next(), symbols.next(), symbols.next()) {
+                (Some(left), Some(right), None) => {
+                    merge_ranks
+                        .entry(left.to_owned())
+                        .or_default()
+                        .entry(right.to_owned())
+                        .or_insert(rank);
+                    rank += 1;
+                }
+                _ => return Err(Error::InvalidBpeMerges { line: index + 1 }),
+            }
+        }
+
+        Ok(BpeTokenizer {
+            vocab,
+            merge_ranks,
+            byte_encoder: byte_encoder(),
+        })
+    }
+
+    /// Tokenizes `text` into token IDs.
+    ///
+    /// Symbols that are missing from the vocabulary are dropped. This can only
+    /// happen if the vocabulary does not contain all 256 byte symbols.
+    pub fn encode(&self, text: &str) -> Vec<i32> {
+        let mut ids = Vec::new();
+        for word in pre_tokenize(text) {
+            let word: String = word
+                .bytes()
+                .map(|byte| self.byte_encoder[byte as usize])
+                .collect();
+            ids.extend(
+                self.merge_word(&word)
+                    .iter()
+                    .filter_map(|symbol| self.vocab.get(symbol)),
+            );
+        }
+        ids
+    }
+
+    /// Applies the merges to `word`, lowest rank first, until none apply.
+    fn merge_word(&self, word: &str) -> Vec<String> {
+        let mut symbols: Vec<String> = word.chars().map(String::from).collect();
+        loop {
+            let best_pair = symbols
+                .windows(2)
+                .filter_map(|pair| {
+                    let rank = self.merge_ranks.get(&pair[0])?.get(&pair[1])?;
+                    Some((rank, &pair[0], &pair[1]))
+                })
+                .min();
+            let (left, right) = match best_pair {
+                Some((_, left, right)) => (left.clone(), right.clone()),
+                None => return symbols,
+            };
+
+            let mut merged = Vec::with_capacity(symbols.len());
+            let mut symbols_iter = symbols.into_iter().peekable();
+            while let Some(symbol) = symbols_iter.next() {
+                if symbol == left && symbols_iter.peek() == Some(&right) {
+                    symbols_iter.next();
+                    merged.push(symbol + &right);
+                } else {
+                    merged.push(symbol);
+                }
+            }
+            symbols = merged;
+        }
+    }
+}
+
+/// Splits `text` into words the way GPT-2 does, i.e. following
+/// `'s|'t|'re|'ve|'m|'ll|'d| ?\p{L}+| ?\p{N}+| ?[^\s\p{L}\p{N}]+|\s+(?!\S)|\s+`.
+///
+/// Letters and numbers are detected with `char::is_alphabetic` and
+/// `char::is_numeric`, which closely approximate the Unicode categories.
+fn pre_tokenize(text: &str) -> Vec<&str> {
+    #[derive(PartialEq)]
+    enum Class {
+        Letter,
+        Number,
+        Other,
+        Whitespace,
+    }
+
+    fn class(character: char) -> Class {
+        if character.is_whitespace() {
+            Class::Whitespace
+        } else if character.is_alphabetic() {
+            Class::Letter
+        } else if character.is_numeric() {
+            Class::Number
+        } else {
+            Class::Other
+        }
+    }
+
+    const CONTRACTIONS: [&str; 7] = ["'s", "'t", "'re", "'ve", "'m", "'ll", "'d"];
+
+    let mut words = Vec::new();
+    let mut rest = text;
+    while let Some(first) = rest.chars().next() {
+        let length = if let Some(contraction) = CONTRACTIONS
+            .iter()
+            .find(|contraction| rest.starts_with(*contraction))
+        {
+            contraction.len()
+        } else {
+            // A single leading space is kept with the word that follows it.
+            let mut characters = rest.char_indices().peekable();
+            let next = rest[first.len_utf8()..].chars().next();
+            if first == ' ' && matches!(next, Some(next) if !next.is_whitespace()) {
+                characters.next();
+            }
+            let (start, start_character) = *characters.peek().unwrap();
+            let start_class = class(start_character);
+            let mut end = start;
+            let mut last = start;
+            while let Some(&(index, character)) = characters.peek() {
+                if class(character) != start_class {
+                    break;
+                }
+                last = index;
+                end = index + character.len_utf8();
+                characters.next();
+            }
+            // A whitespace run followed by a word leaves its last character to
+            // that word (as the optional leading space) or as its own token.
+            if start_class == Class::Whitespace && end < rest.len() && last > start {
+                end = last;
+            }
+            end
+        };
+        words.push(&rest[..length]);
+        rest = &rest[length..];
+    }
+    words
+}
+
+/// Returns GPT-2's reversible mapping from bytes to printable characters.
+///
+/// Printable Latin-1 bytes map to themselves and the remaining bytes are
+/// shifted to code points from U+0100 upwards, so that e.g. a space becomes
+/// `Ġ`.
+fn byte_encoder() -> [char; 256] {
+    let is_printable = |byte: u8| matches!(byte, b'!'..=b'~' | 0xA1..=0xAC | 0xAE..=0xFF);
+    let mut encoder = ['\0'; 256];
+    let mut shifted = 0;
+    for byte in 0..=255u8 {
+        encoder[byte as usize] = if is_printable(byte) {
+            byte as char
+        } else {
+            shifted += 1;
+            std::char::from_u32(255 + shifted).unwrap()
+        };
+    }
+    encoder
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{byte_encoder, pre_tokenize, BpeTokenizer};
+    use crate::errors::Error;
+
+    const VOCAB: &str = r#"{
+        "h": 0, "e": 1, "l": 2, "o": 3, "Ġ": 4, "w": 5, "r": 6, "d": 7,
+        "he": 8, "ll": 9, "llo": 10, "hello": 11, "Ġw": 12, "or": 13, "Ġwor": 14, "!": 15
+    }"#;
+    const MERGES: &str = "#version: 0.2\nh e\nl l\nll o\nhe llo\nĠ w\no r\nĠw or\n";
+
+    #[test]
+    fn encode_applies_merges_by_rank() {
+        let tokenizer = BpeTokenizer::from_vocab_and_merges(VOCAB, MERGES).unwrap();
+        assert_eq!(tokenizer.encode("hello world!"), vec![11, 14, 2, 7, 15]);
+        assert_eq!(tokenizer.encode("hell"), vec![8, 9]);
+        assert!(tokenizer.encode("").is_empty());
+    }
+
+    #[test]
+    fn encode_drops_symbols_missing_from_vocab() {
+        let tokenizer = BpeTokenizer::from_vocab_and_merges(VOCAB, MERGES).unwrap();
+        assert_eq!(tokenizer.encode("hex"), vec![8]);
+    }
+
+    #[test]
+    fn pre_tokenize_matches_gpt2_pattern() {
+        assert_eq!(
+            pre_tokenize("I'm here  now!\n"),
+            vec!["I", "'m", " here", " ", " now", "!", "\n"]
+        );
+        assert_eq!(
+            pre_tokenize("abc123 4.5 don't\t x  "),
+            vec!["abc", "123", " 4", ".", "5", " don", "'t", "\t", " x", "  "]
+        );
+        assert_eq!(pre_tokenize(" \n\nhi"), vec![" \n", "\n", "hi"]);
+    }
+
+    #[test]
+    fn byte_encoder_matches_gpt2() {
+        let encoder = byte_encoder();
+        assert_eq!(encoder[b' ' as usize], 'Ġ');
+        assert_eq!(encoder[b'\n' as usize], 'Ċ');
+        assert_eq!(encoder[b'!' as usize], '!');
+        assert_eq!(encoder[0xC3], 'Ã');
+        assert_eq!(encoder[0xA9], '©');
+        assert_eq!(encoder[0xAD], 'Ń');
+    }
+
+    #[test]
+    fn from_vocab_and_merges_rejects_invalid_vocab() {
+        let result = BpeTokenizer::from_vocab_and_merges(r#"{"a": "zero"}"#, "");
+        assert!(matches!(result, Err(Error::InvalidBpeVocab { .. })));
+    }
+
+    #[test]
+    fn from_vocab_and_merges_rejects_invalid_merges() {
+        let result = BpeTokenizer::from_vocab_and_merges(VOCAB, "#version: 0.2\na b c\n");
+        assert_eq!(result.unwrap_err(), Error::InvalidBpeMerges { line: 2 });
+    }
+}
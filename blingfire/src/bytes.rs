@@ -0,0 +1,96 @@
+use crate::errors::{self, Result};
+use snafu::ensure;
+
+/// Appends each ID in `ids` to `buf` as 4 little-endian bytes, i.e. as the
+/// bytes of the same bits read as a `u32`.
+pub fn encode_to_bytes(ids: &[i32], buf: &mut Vec<u8>) {
+    buf.reserve(ids.len() * 4);
+    for id in ids {
+        buf.extend_from_slice(&id.to_le_bytes());
+    }
+}
+
+/// Reads back token IDs written by `encode_to_bytes`.
+///
+/// ## Errors
+///
+/// Returns `Error::InvalidByteLength` if the length of `buf` is not a multiple of 4.
+///
+/// ## Example
+///
+/// ```
+/// # fn main() -> Result<(), blingfire::Error> {
+///     let mut buf = Vec::new();
+///     blingfire::encode_to_bytes(&[1, 256, -1], &mut buf);
+///     assert_eq!(buf, [1, 0, 0, 0, 0, 1, 0, 0, 255, 255, 255, 255]);
+///     assert_eq!(blingfire::decode_from_bytes(&buf)?, vec![1, 256, -1]);
+///     # Ok(())
+/// # }
+/// ```
+pub fn decode_from_bytes(buf: &[u8]) -> Result<Vec<i32>> {
+    let chunks = buf.chunks_exact(4);
+    ensure!(
+        chunks.remainder().is_empty(),
+        errors::InvalidByteLength { length: buf.len() }
+    );
+    Ok(chunks
+        .map(|bytes| i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .collect())
+}
+
+/// Encodes all sequences in `batch` into one flat byte array (see
+/// `encode_to_bytes`), returning it along with the length in tokens of each
+/// sequence so the batch can be split up again.
+pub fn batch_to_bytes(batch: &[Vec<i32>]) -> (Vec<u8>, Vec<usize>) {
+    let total_tokens = batch.iter().map(Vec::len).sum::<usize>();
+    let mut buf = Vec::with_capacity(total_tokens * 4);
+    let mut lengths = Vec::with_capacity(batch.len());
+    for ids in batch {
+        encode_to_bytes(ids, &mut buf);
+        lengths.push(ids.len());
+    }
+    (buf, lengths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{batch_to_bytes, decode_from_bytes, encode_to_bytes};
+    use crate::errors::Error;
+
+    #[test]
+    fn bytes_round_trip() {
+        let ids = [0, 1, 250_001, i32::MAX, -1, i32::MIN];
+        let mut buf = vec![42];
+        encode_to_bytes(&ids, &mut buf);
+        assert_eq!(buf.len(), 1 + ids.len() * 4);
+        assert_eq!(&buf[1..9], &[0, 0, 0, 0, 1, 0, 0, 0]);
+        assert_eq!(decode_from_bytes(&buf[1..]).unwrap(), ids.to_vec());
+        assert_eq!(decode_from_bytes(&[]).unwrap(), Vec::<i32>::new());
+    }
+
+    #[test]
+    fn decode_from_bytes_rejects_partial_ids() {
+        assert_eq!(
+            decode_from_bytes(&[1, 0, 0, 0, 2]),
+            Err(Error::InvalidByteLength { length: 5 })
+        );
+    }
+
+    #[test]
+    fn batch_bytes_round_trip() {
+        let batch = vec![vec![5, 6, 7], vec![], vec![-8], vec![9, 10]];
+        let (buf, lengths) = batch_to_bytes(&batch);
+        assert_eq!(lengths, vec![3, 0, 1, 2]);
+
+        let ids = decode_from_bytes(&buf).unwrap();
+        let mut offset = 0;
+        let decoded: Vec<Vec<i32>> = lengths
+            .iter()
+            .map(|&len| {
+                offset += len;
+                ids[offset - len..offset].to_vec()
+            })
+            .collect();
+        assert_eq!(decoded, batch);
+    }
+}
@@ -0,0 +1,178 @@
+/// A batch of token ID sequences stored in a compact, delta-encoded form.
+///
+/// Each sequence is stored as the differences between consecutive IDs (the
+/// first ID being relative to zero), zigzag-mapped so that small negative
+/// differences stay small, and written as LEB128 variable-length integers.
+/// Sequences where neighbouring IDs are close, such as the output of a
+/// tokenizer with a frequency-ordered vocabulary, take far fewer than the 4
+/// bytes per ID of a `Vec<i32>`.
+///
+/// ## Example
+///
+/// ```
+/// use blingfire::CompressedTokenBatch;
+///
+/// let batch = vec![vec![100, 101, 103, 97], vec![], vec![250_000]];
+/// let compressed = CompressedTokenBatch::compress(&batch);
+/// assert_eq!(compressed.len(), 3);
+/// assert_eq!(compressed.decompress(), batch);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CompressedTokenBatch {
+    data: Vec<u8>,
+    sequence_lengths: Vec<usize>,
+}
+
+impl CompressedTokenBatch {
+    /// Compresses all sequences in `batch`.
+    pub fn compress(batch: &[Vec<i32>]) -> CompressedTokenBatch {
+        let mut data = Vec::new();
+        let mut sequence_lengths = Vec::with_capacity(batch.len());
+        for ids in batch {
+            let mut previous = 0i32;
+            for &id in ids {
+                write_varint(&mut data, zigzag_encode(id.wrapping_sub(previous)));
+                previous = id;
+            }
+            sequence_lengths.push(ids.len());
+        }
+        CompressedTokenBatch {
+            data,
+            sequence_lengths,
+        }
+    }
+
+    /// Restores the original batch.
+    pub fn decompress(&self) -> Vec<Vec<i32>> {
+        let mut data = self.data.as_slice();
+        self.sequence_lengths
+            .iter()
+            .map(|&length| {
+                let mut previous = 0i32;
+                (0..length)
+                    .map(|_| {
+                        previous = previous.wrapping_add(zigzag_decode(read_varint(&mut data)));
+                        previous
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Returns the number of sequences in the batch.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.sequence_lengths.len()
+    }
+
+    /// Returns whether the batch contains no sequences.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.sequence_lengths.is_empty()
+    }
+
+    /// Returns the length in tokens of each sequence.
+    #[inline]
+    pub fn sequence_lengths(&self) -> &[usize] {
+        &self.sequence_lengths
+    }
+
+    /// Returns the size in bytes of the compressed token IDs.
+    #[inline]
+    pub fn compressed_size(&self) -> usize {
+        self.data.len()
+    }
+}
+
+#[inline]
+fn zigzag_encode(value: i32) -> u32 {
+    ((value << 1) ^ (value >> 31)) as u32
+}
+
+#[inline]
+fn zigzag_decode(value: u32) -> i32 {
+    ((value >> 1) as i32) ^ -((value & 1) as i32)
+}
+
+fn write_varint(data: &mut Vec<u8>, mut value: u32) {
+    while value >= 0x80 {
+        data.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    data.push(value as u8);
+}
+
+/// Reads a varint written by `write_varint` from the front of `data`. The data
+/// is only ever produced by `compress`, so it is always well formed.
+fn read_varint(data: &mut &[u8]) -> u32 {
+    let mut value = 0;
+    let mut shift = 0;
+    loop {
+        let byte = data[0];
+        *data = &data[1..];
+        value |= u32::from(byte & 0x7f) << shift;
+        if byte < 0x80 {
+            return value;
+        }
+        shift += 7;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{zigzag_decode, zigzag_encode, CompressedTokenBatch};
+
+    #[test]
+    fn compress_round_trip() {
+        let batch = vec![
+            vec![0, 1, 2, 3],
+            vec![],
+            vec![250_001, 5, 250_001, -7],
+            vec![i32::MAX, i32::MIN, 0, i32::MIN, i32::MAX],
+        ];
+        let compressed = CompressedTokenBatch::compress(&batch);
+        assert_eq!(compressed.len(), 4);
+        assert_eq!(compressed.sequence_lengths(), &[4, 0, 4, 5]);
+        assert_eq!(compressed.decompress(), batch);
+    }
+
+    #[test]
+    fn compress_empty_batch() {
+        let compressed = CompressedTokenBatch::compress(&[]);
+        assert!(compressed.is_empty());
+        assert_eq!(compressed.compressed_size(), 0);
+        assert_eq!(compressed.decompress(), Vec::<Vec<i32>>::new());
+    }
+
+    #[test]
+    fn compress_is_smaller_than_uncompressed() {
+        // IDs that stay close to each other, as is common for frequent tokens.
+        let batch: Vec<Vec<i32>> = (0..100)
+            .map(|row| {
+                (0..64)
+                    .map(|column| 1_000 + (row * 7 + column * 13) % 50)
+                    .collect()
+            })
+            .collect();
+        let uncompressed_size = batch.len() * 64 * std::mem::size_of::<i32>();
+        let compressed = CompressedTokenBatch::compress(&batch);
+        assert!(compressed.compressed_size() * 3 < uncompressed_size);
+        assert_eq!(compressed.decompress(), batch);
+    }
+
+    #[test]
+    fn zigzag_maps_small_magnitudes_to_small_values() {
+        let pairs = [
+            (0, 0),
+            (-1, 1),
+            (1, 2),
+            (-2, 3),
+            (i32::MAX, u32::MAX - 1),
+            (i32::MIN, u32::MAX),
+        ];
+        for &(value, encoded) in &pairs {
+            assert_eq!(zigzag_encode(value), encoded);
+            assert_eq!(zigzag_decode(encoded), value);
+        }
+    }
+}
@@ -14,6 +14,23 @@ pub enum Error {
         "An unknown error caused the tokenizer to fail (the C++ function returned -1)."
     ))]
     UnknownError,
+
+    /// The output of the C++ function broke an invariant the wrapper relies on to access the
+    /// output buffer safely.
+    #[snafu(display("An FFI invariant was violated: {}.", reason))]
+    FfiInvariantViolation { reason: &'static str },
+
+    /// The BPE vocabulary is not a JSON object mapping tokens to integer IDs.
+    #[snafu(display("Invalid BPE vocabulary: {}.", reason))]
+    InvalidBpeVocab { reason: String },
+
+    /// A line of the BPE merges is not a pair of space-separated symbols.
+    #[snafu(display("Invalid BPE merges: line {} is not a pair of symbols.", line))]
+    InvalidBpeMerges { line: usize },
+
+    /// A byte buffer of token IDs has a length that is not a multiple of 4.
+    #[snafu(display("Byte buffer length {} is not a multiple of 4.", length))]
+    InvalidByteLength { length: usize },
 }
 
 /// Result of calling the tokenizer functions.
@@ -0,0 +1,106 @@
+use std::collections::{HashMap, HashSet};
+
+/// Decides which token IDs to keep after tokenization, see `filter_token_ids`.
+pub trait TokenFilter: Send + Sync {
+    /// Returns whether `id` should be kept.
+    fn keep(&self, id: i32) -> bool;
+}
+
+/// Keeps token IDs up to and including the given maximum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MaxIdFilter(pub i32);
+
+impl TokenFilter for MaxIdFilter {
+    #[inline]
+    fn keep(&self, id: i32) -> bool {
+        id <= self.0
+    }
+}
+
+/// Drops the token IDs in the set, e.g. punctuation for bag-of-words models.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ExcludeIdsFilter(pub HashSet<i32>);
+
+impl TokenFilter for ExcludeIdsFilter {
+    #[inline]
+    fn keep(&self, id: i32) -> bool {
+        !self.0.contains(&id)
+    }
+}
+
+/// Keeps token IDs that occur at least `min_freq` times according to
+/// `freq_map`. IDs missing from the map count as never seen.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MinFrequencyFilter {
+    pub freq_map: HashMap<i32, usize>,
+    pub min_freq: usize,
+}
+
+impl TokenFilter for MinFrequencyFilter {
+    #[inline]
+    fn keep(&self, id: i32) -> bool {
+        self.freq_map.get(&id).cloned().unwrap_or(0) >= self.min_freq
+    }
+}
+
+/// Removes the token IDs in `ids` that `filter` does not keep, preserving the
+/// order of the rest.
+///
+/// ## Example
+///
+/// ```
+/// use blingfire::{filter_token_ids, ExcludeIdsFilter};
+///
+/// let mut ids = vec![5, 8, 6, 8];
+/// filter_token_ids(&mut ids, &ExcludeIdsFilter(vec![8].into_iter().collect()));
+/// assert_eq!(ids, vec![5, 6]);
+/// ```
+#[inline]
+pub fn filter_token_ids<F: TokenFilter + ?Sized>(ids: &mut Vec<i32>, filter: &F) {
+    ids.retain(|&id| filter.keep(id));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{filter_token_ids, ExcludeIdsFilter, MaxIdFilter, MinFrequencyFilter, TokenFilter};
+
+    #[test]
+    fn max_id_filter_is_inclusive() {
+        let mut ids = vec![3, 10, 11, -1, 10];
+        filter_token_ids(&mut ids, &MaxIdFilter(10));
+        assert_eq!(ids, vec![3, 10, -1, 10]);
+    }
+
+    #[test]
+    fn exclude_ids_filter_removes_excluded() {
+        let excluded = ExcludeIdsFilter([4, 5].iter().cloned().collect());
+        let mut ids = vec![1, 4, 2, 5, 4, 3];
+        filter_token_ids(&mut ids, &excluded);
+        assert_eq!(ids, vec![1, 2, 3]);
+        assert!(ids.iter().all(|id| !excluded.0.contains(id)));
+    }
+
+    #[test]
+    fn min_frequency_filter_drops_rare_ids() {
+        let filter = MinFrequencyFilter {
+            freq_map: [(1, 10), (2, 2), (3, 3)].iter().cloned().collect(),
+            min_freq: 3,
+        };
+        let mut ids = vec![1, 2, 3, 4];
+        filter_token_ids(&mut ids, &filter);
+        assert_eq!(ids, vec![1, 3]);
+    }
+
+    #[test]
+    fn filters_work_as_trait_objects() {
+        let filters: Vec<Box<dyn TokenFilter>> = vec![
+            Box::new(MaxIdFilter(5)),
+            Box::new(ExcludeIdsFilter::default()),
+        ];
+        let mut ids = vec![1, 6, 5];
+        for filter in &filters {
+            filter_token_ids(&mut ids, filter.as_ref());
+        }
+        assert_eq!(ids, vec![1, 5]);
+    }
+}
@@ -0,0 +1,86 @@
+/// Replaces every token ID outside `[0, vocab_size)` with `0`.
+///
+/// Use this before looking IDs up in an embedding table of `vocab_size` rows.
+/// See `clip_ids_with_map` to choose the replacement ID and count replacements.
+#[inline]
+pub fn clip_token_ids(ids: &mut [i32], vocab_size: usize) {
+    clip_ids_with_map(ids, vocab_size, 0);
+}
+
+/// Replaces every token ID outside `[0, vocab_size)` with `fallback` and
+/// returns the number of IDs that were replaced.
+///
+/// ## Example
+///
+/// ```
+/// let mut ids = [4, -1, 7, 12];
+/// assert_eq!(blingfire::clip_ids_with_map(&mut ids, 10, 3), 2);
+/// assert_eq!(ids, [4, 3, 7, 3]);
+/// ```
+pub fn clip_ids_with_map(ids: &mut [i32], vocab_size: usize, fallback: i32) -> usize {
+    let mut replaced = 0;
+    for id in ids.iter_mut() {
+        if *id < 0 || *id as usize >= vocab_size {
+            *id = fallback;
+            replaced += 1;
+        }
+    }
+    replaced
+}
+
+/// Returns whether two token ID sequences are identical.
+///
+/// This is the same as `a == b`, which the standard library already lowers to a
+/// `memcmp` for `i32` slices. The named function exists so that consistency
+/// checks read clearly and can be found easily, e.g. in logging or property
+/// tests comparing two tokenizations of the same text.
+#[inline]
+pub fn ids_equal(a: &[i32], b: &[i32]) -> bool {
+    a == b
+}
+
+/// Returns whether `ids` starts with the token ID sequence `prefix`.
+#[inline]
+pub fn ids_match_prefix(ids: &[i32], prefix: &[i32]) -> bool {
+    ids.starts_with(prefix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{clip_ids_with_map, clip_token_ids, ids_equal, ids_match_prefix};
+
+    #[test]
+    fn clip_token_ids_replaces_out_of_range_with_zero() {
+        let mut ids = [5, -3, 9, 10, i32::MAX, i32::MIN, 0];
+        clip_token_ids(&mut ids, 10);
+        assert_eq!(ids, [5, 0, 9, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn clip_ids_with_map_counts_replacements() {
+        let mut ids = vec![1, 2, 100, -1, 3, 50];
+        assert_eq!(clip_ids_with_map(&mut ids, 50, 7), 3);
+        assert_eq!(ids, vec![1, 2, 7, 7, 3, 7]);
+
+        let mut in_range = vec![0, 1, 49];
+        assert_eq!(clip_ids_with_map(&mut in_range, 50, 7), 0);
+        assert_eq!(in_range, vec![0, 1, 49]);
+    }
+
+    #[test]
+    fn ids_equal_compares_whole_sequences() {
+        assert!(ids_equal(&[1, 2, 3], &[1, 2, 3]));
+        assert!(ids_equal(&[], &[]));
+        assert!(!ids_equal(&[1, 2, 3], &[1, 2, 4]));
+        assert!(!ids_equal(&[1, 2, 3], &[1, 2]));
+    }
+
+    #[test]
+    fn ids_match_prefix_checks_leading_ids() {
+        assert!(ids_match_prefix(&[1, 2, 3], &[1, 2]));
+        assert!(ids_match_prefix(&[1, 2, 3], &[]));
+        assert!(ids_match_prefix(&[1, 2, 3], &[1, 2, 3]));
+        assert!(!ids_match_prefix(&[1, 2, 3], &[2, 3]));
+        assert!(!ids_match_prefix(&[1, 2], &[1, 2, 3]));
+    }
+}
@@ -1,7 +1,20 @@
 //! blingfire is a thin Rust wrapper for the
 //! [BlingFire](https://github.com/microsoft/BlingFire) tokenization library.
 
+#[cfg(feature = "bpe")]
+pub mod bpe;
+mod bytes;
+mod compressed;
 mod errors;
+mod filters;
+mod ids;
+mod one_hot;
+mod pipeline;
+mod preprocessing;
+mod stats;
+#[cfg(feature = "tch")]
+mod tensor;
+mod truncation;
 
 use blingfire_sys::{
     FALimits_MaxArrSize as FA_LIMITS_MAX_ARRAY_SIZE, TextToSentences as text_to_sentences_ffi,
@@ -14,7 +27,28 @@ use std::{
     os::raw::{c_char, c_int},
 };
 
-pub use crate::errors::{Error, Result};
+pub use crate::{
+    bytes::{batch_to_bytes, decode_from_bytes, encode_to_bytes},
+    compressed::CompressedTokenBatch,
+    errors::{Error, Result},
+    filters::{filter_token_ids, ExcludeIdsFilter, MaxIdFilter, MinFrequencyFilter, TokenFilter},
+    ids::{clip_ids_with_map, clip_token_ids, ids_equal, ids_match_prefix},
+    one_hot::ids_to_one_hot_sparse,
+    pipeline::TokenizerPipeline,
+    preprocessing::{
+        normalize_whitespace, strip_html, LowerCase, NormalizeWhitespace, Processor, StripHtml,
+    },
+    stats::{
+        batch_oov_rate, batch_statistics, count_oov_tokens, histogram, oov_rate, top_k_tokens,
+        BatchStats,
+    },
+    truncation::{batch_truncate, truncate_at_sentence_end, truncate_from_center, TruncationSide},
+};
+
+#[cfg(feature = "ndarray")]
+pub use crate::one_hot::ids_to_one_hot;
+#[cfg(feature = "tch")]
+pub use crate::tensor::{batch_to_tensor, batch_to_tensor_on_device};
 
 /// The maximum valid size of the input text for the tokenizer functions.
 /// Re-exported from the C++ library.
@@ -23,7 +57,8 @@ pub const MAX_TEXT_LENGTH: usize = FA_LIMITS_MAX_ARRAY_SIZE as usize;
 /// Tokenizes a piece of text into words separated by whitespace.
 ///
 /// The result of the tokenization operation is stored in the string
-/// `destination`. The string will first be cleared.
+/// `destination`. The string will first be cleared. `source` can be any string
+/// type, e.g. `&str`, `String`, `Cow<str>` or `Arc<str>`.
 ///
 /// ## Errors
 ///
@@ -41,14 +76,15 @@ pub const MAX_TEXT_LENGTH: usize = FA_LIMITS_MAX_ARRAY_SIZE as usize;
 /// # }
 /// ```
 #[inline]
-pub fn text_to_words(source: &str, destination: &mut String) -> Result<()> {
-    tokenize(text_to_words_ffi, source, destination)
+pub fn text_to_words<S: AsRef<str>>(source: S, destination: &mut String) -> Result<()> {
+    tokenize(text_to_words_ffi, source.as_ref(), destination)
 }
 
 /// Tokenizes a piece of text into sentences separated by whitespace.
 ///
 /// The result of the tokenization operation is stored in the string
-/// `destination`. The string will first be cleared.
+/// `destination`. The string will first be cleared. `source` can be any string
+/// type, e.g. `&str`, `String`, `Cow<str>` or `Arc<str>`.
 ///
 /// ## Errors
 ///
@@ -66,8 +102,8 @@ pub fn text_to_words(source: &str, destination: &mut String) -> Result<()> {
 /// # }
 /// ```
 #[inline]
-pub fn text_to_sentences(source: &str, destination: &mut String) -> Result<()> {
-    tokenize(text_to_sentences_ffi, source, destination)
+pub fn text_to_sentences<S: AsRef<str>>(source: S, destination: &mut String) -> Result<()> {
+    tokenize(text_to_sentences_ffi, source.as_ref(), destination)
 }
 
 type Tokenizer = unsafe extern "C" fn(*const c_char, c_int, *mut c_char, c_int) -> c_int;
@@ -89,33 +125,21 @@ where
             max_text_length: MAX_TEXT_LENGTH
         }
     );
-    let source_len = source_len as c_int;
-
     loop {
-        let length = unsafe {
-            tokenizer(
-                source.as_ptr() as *const c_char,
-                source_len,
-                destination.as_mut_ptr() as *mut c_char,
-                destination.capacity().try_into().unwrap_or(i32::MAX),
-            )
-        };
-
-        // The C++ function returned -1, an unknown error.
-        ensure!(length > 0, errors::UnknownError);
-
-        if length as usize > destination.capacity() {
+        let length = tokenize_ffi(tokenizer, source, destination)?;
+
+        if length > destination.capacity() {
             // There was not enough capacity in `destination` to store the parsed text.
             // Although the C++ function allocated an internal buffer with the parsed text, that's
             // not exposed. We'll have to reserve `length` bytes in `destination` (as
             // `destination.len() == 0`) and parse the `source` string again.
-            destination.reserve_exact(length as usize);
+            destination.reserve_exact(length);
             continue;
         } else {
             // The text was successfully parsed, set the length to the return value (-1 for the
             // null character).
             unsafe {
-                destination.as_mut_vec().set_len(length as usize - 1);
+                destination.as_mut_vec().set_len(length - 1);
             }
             break;
         }
@@ -124,9 +148,54 @@ where
     Ok(())
 }
 
+/// Calls `tokenizer` on `source`, writing into the spare capacity of the (empty) `destination`,
+/// and returns the output length reported by the C++ function (including the null character).
+///
+/// If the reported length fits in the capacity that was passed in, the null character is checked
+/// to be where the C++ function says it is. Any violated invariant is reported as
+/// `Error::FfiInvariantViolation` rather than risking reading out of bounds.
+#[inline]
+fn tokenize_ffi(tokenizer: Tokenizer, source: &str, destination: &mut String) -> Result<usize> {
+    debug_assert!(destination.is_empty());
+
+    let source_ptr = source.as_ptr() as *const c_char;
+    let destination_ptr = destination.as_mut_ptr() as *mut c_char;
+
+    // `tokenize` rejects sources longer than `MAX_TEXT_LENGTH`, which fits in a C++ `int`.
+    debug_assert!(source.len() <= c_int::MAX as usize);
+    let source_len = source.len() as c_int;
+
+    // Advertising less capacity than is really available is safe: the C++ function never writes
+    // past the size it is given.
+    let capacity: c_int = destination.capacity().try_into().unwrap_or(i32::MAX);
+
+    let length = unsafe { tokenizer(source_ptr, source_len, destination_ptr, capacity) };
+
+    // The C++ function returned -1, an unknown error.
+    ensure!(length > 0, errors::UnknownError);
+
+    let length = length as usize;
+    if length <= capacity as usize {
+        let terminator = unsafe { *destination.as_ptr().add(length - 1) };
+        ensure!(
+            terminator == 0,
+            errors::FfiInvariantViolation {
+                reason: "output is not null terminated at the returned length"
+            }
+        );
+    }
+
+    Ok(length)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{errors::Error, text_to_sentences, text_to_words, MAX_TEXT_LENGTH};
+    use super::{errors::Error, text_to_sentences, text_to_words, tokenize, MAX_TEXT_LENGTH};
+    use std::{
+        borrow::Cow,
+        os::raw::{c_char, c_int},
+        sync::Arc,
+    };
 
     const TEST_TEXT: &str = "I think. Sometimes, that my use of\ncommas, (and, occasionally, exclamation marks) can be excessive!!";
     const TEST_TEXT_WORDS: &str = "I think . Sometimes , that my use of commas , ( and , occasionally , exclamation marks ) can be excessive ! !";
@@ -172,6 +241,19 @@ mod tests {
         assert_eq!(initial_capacity, parsed.capacity());
     }
 
+    #[test]
+    fn text_to_words_long_input() {
+        // The output needs several times the capacity of the initial buffer, so this exercises
+        // the reserve-and-retry path on a large input.
+        let source = vec![TEST_TEXT; 20].join(" ");
+        let expected = vec![TEST_TEXT_WORDS; 20].join(" ");
+        assert!(source.len() > 500);
+
+        let mut parsed = String::with_capacity(16);
+        text_to_words(&source, &mut parsed).unwrap();
+        assert_eq!(expected, parsed);
+    }
+
     #[test]
     fn text_to_words_string_too_long() {
         let source = String::from_utf8(vec![b'.'; MAX_TEXT_LENGTH + 1]).unwrap();
@@ -186,6 +268,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn text_to_words_accepts_string_types() {
+        let owned = TEST_TEXT.to_owned();
+        let cow: Cow<str> = Cow::Borrowed(TEST_TEXT);
+        let shared: Arc<str> = Arc::from(TEST_TEXT);
+        let boxed: Box<str> = Box::from(TEST_TEXT);
+
+        let mut parsed = String::new();
+        text_to_words(&owned, &mut parsed).unwrap();
+        assert_eq!(TEST_TEXT_WORDS, parsed.as_str());
+        text_to_words(owned, &mut parsed).unwrap();
+        assert_eq!(TEST_TEXT_WORDS, parsed.as_str());
+        text_to_words(cow, &mut parsed).unwrap();
+        assert_eq!(TEST_TEXT_WORDS, parsed.as_str());
+        text_to_words(shared, &mut parsed).unwrap();
+        assert_eq!(TEST_TEXT_WORDS, parsed.as_str());
+        text_to_words(boxed, &mut parsed).unwrap();
+        assert_eq!(TEST_TEXT_WORDS, parsed.as_str());
+    }
+
     #[test]
     fn text_to_sentences_new_string() {
         let mut parsed = String::new();
@@ -225,4 +327,127 @@ mod tests {
         assert_eq!(TEST_TEXT_SENTENCES, parsed.as_str());
         assert_eq!(initial_capacity, parsed.capacity());
     }
+
+    unsafe extern "C" fn returns_minus_one(
+        _: *const c_char,
+        _: c_int,
+        _: *mut c_char,
+        _: c_int,
+    ) -> c_int {
+        -1
+    }
+
+    unsafe extern "C" fn returns_zero(
+        _: *const c_char,
+        _: c_int,
+        _: *mut c_char,
+        _: c_int,
+    ) -> c_int {
+        0
+    }
+
+    unsafe extern "C" fn omits_null_character(
+        _: *const c_char,
+        _: c_int,
+        destination: *mut c_char,
+        capacity: c_int,
+    ) -> c_int {
+        for index in 0..capacity as usize {
+            *destination.add(index) = b'x' as c_char;
+        }
+        1
+    }
+
+    #[test]
+    fn tokenize_unknown_error() {
+        let mut destination = String::new();
+        let result = tokenize(returns_minus_one, "hello", &mut destination);
+        assert_eq!(Err(Error::UnknownError), result);
+    }
+
+    #[test]
+    fn tokenize_zero_length_is_unknown_error() {
+        let mut destination = String::new();
+        let result = tokenize(returns_zero, "hello", &mut destination);
+        assert_eq!(Err(Error::UnknownError), result);
+    }
+
+    #[test]
+    fn tokenize_missing_null_character() {
+        let mut destination = String::with_capacity(16);
+        let result = tokenize(omits_null_character, "hello", &mut destination);
+        assert_eq!(
+            Err(Error::FfiInvariantViolation {
+                reason: "output is not null terminated at the returned length"
+            }),
+            result
+        );
+        assert!(destination.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod determinism_tests {
+    use super::{text_to_sentences, text_to_words};
+    use std::thread;
+
+    const NUM_THREADS: usize = 8;
+    const ITERATIONS: usize = 50;
+
+    const TEST_TEXTS: &[&str] = &[
+        "I think. Sometimes, that my use of\ncommas, (and, occasionally, exclamation marks) can be excessive!!",
+        "Cat,sat on   the mat. Dog barked.",
+        "Ünïcödé text — with dashes, “quotes” and emoji 😀 works too.",
+        "Mr. Smith went to Washington D.C. on Jan. 5th. He left at 3 p.m.",
+    ];
+
+    type Tokenize = fn(&str, &mut String) -> crate::Result<()>;
+
+    fn tokenize_all(tokenize: Tokenize) -> Vec<String> {
+        TEST_TEXTS
+            .iter()
+            .map(|text| {
+                let mut parsed = String::new();
+                tokenize(text, &mut parsed).unwrap();
+                parsed
+            })
+            .collect()
+    }
+
+    fn assert_deterministic_across_threads(tokenize: Tokenize) {
+        let sequential = tokenize_all(tokenize);
+        assert_eq!(sequential, tokenize_all(tokenize));
+
+        let per_thread: Vec<Vec<Vec<String>>> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..NUM_THREADS)
+                .map(|_| {
+                    scope.spawn(|| {
+                        (0..ITERATIONS)
+                            .map(|_| tokenize_all(tokenize))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect()
+        });
+
+        for outputs in per_thread {
+            for output in outputs {
+                assert_eq!(sequential, output);
+            }
+        }
+    }
+
+    #[test]
+    fn text_to_words_is_deterministic_across_threads() {
+        assert_deterministic_across_threads(|text, parsed| text_to_words(text, parsed));
+    }
+
+    #[test]
+    fn text_to_sentences_is_deterministic_across_threads() {
+        assert_deterministic_across_threads(|text, parsed| text_to_sentences(text, parsed));
+    }
 }
@@ -0,0 +1,63 @@
+/// One-hot encodes `ids` as a `[ids.len(), vocab_size]` matrix.
+///
+/// Row `i` is all zeros except for a `1.0` in column `ids[i]`.
+///
+/// ## Panics
+///
+/// Panics if any ID is outside `[0, vocab_size)`. Use `clip_token_ids` first if
+/// the IDs might be out of range.
+#[cfg(feature = "ndarray")]
+pub fn ids_to_one_hot(ids: &[i32], vocab_size: usize) -> ndarray::Array2<f32> {
+    let mut one_hot = ndarray::Array2::zeros((ids.len(), vocab_size));
+    for (position, &id) in ids.iter().enumerate() {
+        assert!(
+            id >= 0 && (id as usize) < vocab_size,
+            "token ID {} at position {} is outside [0, {})",
+            id,
+            position,
+            vocab_size
+        );
+        one_hot[[position, id as usize]] = 1.0;
+    }
+    one_hot
+}
+
+/// Sparse one-hot encoding of `ids` as `(position, id)` pairs, i.e. the
+/// coordinates of the ones in the `ids_to_one_hot` matrix.
+#[inline]
+pub fn ids_to_one_hot_sparse(ids: &[i32]) -> Vec<(usize, i32)> {
+    ids.iter().cloned().enumerate().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ids_to_one_hot_sparse;
+
+    #[cfg(feature = "ndarray")]
+    #[test]
+    fn ids_to_one_hot_has_a_single_one_per_row() {
+        let ids = [3, 0, 4, 3];
+        let one_hot = super::ids_to_one_hot(&ids, 5);
+        assert_eq!(one_hot.shape(), &[4, 5]);
+        for (position, row) in one_hot.outer_iter().enumerate() {
+            assert_eq!(row.sum(), 1.0);
+            assert_eq!(row[ids[position] as usize], 1.0);
+        }
+    }
+
+    #[cfg(feature = "ndarray")]
+    #[test]
+    #[should_panic(expected = "token ID 5 at position 1 is outside [0, 5)")]
+    fn ids_to_one_hot_panics_on_out_of_range_id() {
+        super::ids_to_one_hot(&[1, 5], 5);
+    }
+
+    #[test]
+    fn ids_to_one_hot_sparse_pairs_positions_with_ids() {
+        assert_eq!(
+            ids_to_one_hot_sparse(&[7, 2, 7]),
+            vec![(0, 7), (1, 2), (2, 7)]
+        );
+        assert_eq!(ids_to_one_hot_sparse(&[]), vec![]);
+    }
+}
@@ -0,0 +1,150 @@
+use crate::{
+    errors::Result, preprocessing::Processor, text_to_sentences_ffi, text_to_words_ffi, tokenize,
+    Tokenizer,
+};
+use std::borrow::Cow;
+
+/// A chain of `Processor`s followed by one of the BlingFire tokenizers.
+///
+/// The processors are run in the order they were added with `pipe`, and the
+/// result is then tokenized.
+///
+/// ## Example
+///
+/// ```
+/// # fn main() -> Result<(), blingfire::Error> {
+///     use blingfire::{LowerCase, NormalizeWhitespace, TokenizerPipeline};
+///
+///     let pipeline = TokenizerPipeline::words()
+///         .pipe(NormalizeWhitespace)
+///         .pipe(LowerCase);
+///     let mut parsed = String::new();
+///     pipeline.tokenize("Cat,sat on   the  MAT.", &mut parsed)?;
+///     assert_eq!(parsed.as_str(), "cat , sat on the mat .");
+///     # Ok(())
+/// # }
+/// ```
+pub struct TokenizerPipeline {
+    tokenizer: Tokenizer,
+    processors: Vec<Box<dyn Processor>>,
+}
+
+impl TokenizerPipeline {
+    /// Creates a pipeline that ends with `text_to_words`.
+    pub fn words() -> Self {
+        Self::new(text_to_words_ffi)
+    }
+
+    /// Creates a pipeline that ends with `text_to_sentences`.
+    pub fn sentences() -> Self {
+        Self::new(text_to_sentences_ffi)
+    }
+
+    fn new(tokenizer: Tokenizer) -> Self {
+        Self {
+            tokenizer,
+            processors: Vec::new(),
+        }
+    }
+
+    /// Appends `processor` to the chain, to run after the ones already added.
+    pub fn pipe<P: Processor + 'static>(mut self, processor: P) -> Self {
+        self.processors.push(Box::new(processor));
+        self
+    }
+
+    /// Runs all processors on `text`, in order, without tokenizing it.
+    pub fn process<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let mut text = Cow::Borrowed(text);
+        for processor in &self.processors {
+            text = match text {
+                Cow::Borrowed(text) => processor.process(text),
+                Cow::Owned(text) => match processor.process(&text) {
+                    // The processor may borrow a part of its input (or any other string), so only
+                    // the exact same slice means the text is unchanged.
+                    Cow::Borrowed(processed)
+                        if processed.as_ptr() == text.as_ptr() && processed.len() == text.len() =>
+                    {
+                        Cow::Owned(text)
+                    }
+                    processed => Cow::Owned(processed.into_owned()),
+                },
+            };
+        }
+        text
+    }
+
+    /// Runs all processors on `source` and tokenizes the result into
+    /// `destination`, which will first be cleared.
+    ///
+    /// ## Errors
+    ///
+    /// Fails in the same cases as the underlying tokenizer, with the size limit
+    /// applying to the processed text.
+    pub fn tokenize(&self, source: &str, destination: &mut String) -> Result<()> {
+        tokenize(self.tokenizer, &self.process(source), destination)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TokenizerPipeline;
+    use crate::{LowerCase, NormalizeWhitespace, Processor};
+    use std::borrow::Cow;
+
+    struct Append(&'static str);
+
+    impl Processor for Append {
+        fn process<'a>(&self, text: &'a str) -> Cow<'a, str> {
+            Cow::Owned(format!("{}{}", text, self.0))
+        }
+    }
+
+    struct Trim;
+
+    impl Processor for Trim {
+        fn process<'a>(&self, text: &'a str) -> Cow<'a, str> {
+            Cow::Borrowed(text.trim())
+        }
+    }
+
+    #[test]
+    fn pipeline_without_processors_borrows() {
+        let pipeline = TokenizerPipeline::words();
+        assert!(matches!(
+            pipeline.process("Hello  World"),
+            Cow::Borrowed("Hello  World")
+        ));
+    }
+
+    #[test]
+    fn pipeline_applies_processors_in_order() {
+        let pipeline = TokenizerPipeline::words()
+            .pipe(Append(" One"))
+            .pipe(LowerCase)
+            .pipe(Append(" Two"));
+        assert_eq!(pipeline.process("Zero"), "zero one Two");
+    }
+
+    #[test]
+    fn pipeline_keeps_borrowed_subslices() {
+        let pipeline = TokenizerPipeline::words().pipe(Trim);
+        assert!(matches!(pipeline.process("  x  "), Cow::Borrowed("x")));
+
+        let pipeline = TokenizerPipeline::words()
+            .pipe(Append("  "))
+            .pipe(Trim)
+            .pipe(LowerCase);
+        assert_eq!(pipeline.process("  X"), "x");
+    }
+
+    #[test]
+    fn pipeline_tokenizes_processed_text() {
+        let pipeline = TokenizerPipeline::sentences().pipe(NormalizeWhitespace);
+        let mut parsed = String::new();
+        pipeline
+            .tokenize("  Cat sat.\n\nDog   barked. ", &mut parsed)
+            .unwrap();
+        assert_eq!(parsed.as_str(), "Cat sat.\nDog barked.");
+    }
+}
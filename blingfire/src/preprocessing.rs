@@ -0,0 +1,523 @@
+use std::borrow::Cow;
+
+/// Collapses consecutive whitespace into a single space and strips leading and
+/// trailing whitespace.
+///
+/// Any Unicode whitespace character (including `\t`, `\n` and `\r`) is treated
+/// as a separator. If `source` is already normalized it is returned as
+/// `Cow::Borrowed` without allocating.
+///
+/// ## Example
+///
+/// ```
+/// let normalized = blingfire::normalize_whitespace("  hello  world\t!\n");
+/// assert_eq!(normalized, "hello world !");
+/// ```
+pub fn normalize_whitespace(source: &str) -> Cow<'_, str> {
+    if is_whitespace_normalized(source) {
+        return Cow::Borrowed(source);
+    }
+
+    let mut normalized = String::with_capacity(source.len());
+    for word in source.split_whitespace() {
+        if !normalized.is_empty() {
+            normalized.push(' ');
+        }
+        normalized.push_str(word);
+    }
+    Cow::Owned(normalized)
+}
+
+fn is_whitespace_normalized(source: &str) -> bool {
+    // Start as if preceded by a space, so that leading whitespace is rejected.
+    let mut previous_is_space = true;
+    for character in source.chars() {
+        if character.is_whitespace() {
+            if character != ' ' || previous_is_space {
+                return false;
+            }
+            previous_is_space = true;
+        } else {
+            previous_is_space = false;
+        }
+    }
+    source.is_empty() || !previous_is_space
+}
+
+/// A text transformation applied before tokenization.
+///
+/// Processors return `Cow::Borrowed` when they leave the text unchanged, so a
+/// chain of processors only allocates when some step actually rewrites the text.
+/// Returning a borrowed part of `text`, e.g. `Cow::Borrowed(text.trim())`, is
+/// also fine. See `TokenizerPipeline` for chaining processors with a tokenizer.
+pub trait Processor: Send + Sync {
+    /// Transforms `text`.
+    fn process<'a>(&self, text: &'a str) -> Cow<'a, str>;
+}
+
+/// Processor that collapses whitespace, see `normalize_whitespace`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NormalizeWhitespace;
+
+impl Processor for NormalizeWhitespace {
+    #[inline]
+    fn process<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        normalize_whitespace(text)
+    }
+}
+
+/// Processor that converts text to lowercase.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LowerCase;
+
+impl Processor for LowerCase {
+    fn process<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let is_lowercase = text.chars().all(|character| {
+            let mut lowercase = character.to_lowercase();
+            lowercase.next() == Some(character) && lowercase.next().is_none()
+        });
+        if is_lowercase {
+            Cow::Borrowed(text)
+        } else {
+            Cow::Owned(text.to_lowercase())
+        }
+    }
+}
+
+/// Removes HTML markup from `source`.
+///
+/// Tags (including their attributes) and comments are dropped, the contents of
+/// `<script>` and `<style>` elements are dropped along with their tags, and
+/// character references such as `&amp;`, `&#169;` or `&#xA9;` are decoded. A
+/// `<` or `&` that does not start markup is kept as is. Inline tags such as
+/// `<b>` are removed without a trace, while block and line break tags such as
+/// `<p>` or `<br>` separate the text around them with a space.
+///
+/// If `source` contains no markup candidates it is returned as
+/// `Cow::Borrowed` without allocating. Malformed markup does not affect the
+/// running time, which is linear in the length of `source`.
+///
+/// ## Example
+///
+/// ```
+/// let stripped = blingfire::strip_html("<p>Tom &amp; <b>Jerry</b></p><p>bye</p>");
+/// assert_eq!(stripped, "Tom & Jerry bye");
+/// ```
+pub fn strip_html(source: &str) -> Cow<'_, str> {
+    let is_markup_start = |character| character == '<' || character == '&';
+    if !source.contains(is_markup_start) {
+        return Cow::Borrowed(source);
+    }
+
+    let mut stripped = StrippedText::with_capacity(source.len());
+    // Offset in `source` before which a failed tag scan showed that no tag
+    // can end, so tag candidates there are not scanned again.
+    let mut no_tags_before = 0;
+    let mut rest = source;
+    while let Some(position) = rest.find(is_markup_start) {
+        stripped.push_str(&rest[..position]);
+        rest = &rest[position..];
+        rest = if let Some(after_ampersand) = rest.strip_prefix('&') {
+            match decode_html_entity(rest) {
+                Some((decoded, length)) => {
+                    stripped.push_str(decoded.encode_utf8(&mut [0; 4]));
+                    &rest[length..]
+                }
+                None => {
+                    stripped.push_str("&");
+                    after_ampersand
+                }
+            }
+        } else {
+            let offset = source.len() - rest.len();
+            let markup = if offset >= no_tags_before || rest.starts_with("<!--") {
+                skip_html_markup(rest)
+            } else {
+                HtmlMarkup::NotMarkup { scanned: 1 }
+            };
+            match markup {
+                HtmlMarkup::Skipped { rest, separates } => {
+                    stripped.separate |= separates;
+                    rest
+                }
+                HtmlMarkup::NotMarkup { scanned } => {
+                    no_tags_before = no_tags_before.max(offset + scanned);
+                    stripped.push_str("<");
+                    &rest[1..]
+                }
+            }
+        };
+    }
+    stripped.push_str(rest);
+    Cow::Owned(stripped.text)
+}
+
+/// Processor that removes HTML markup, see `strip_html`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StripHtml;
+
+impl Processor for StripHtml {
+    #[inline]
+    fn process<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        strip_html(text)
+    }
+}
+
+/// Elements whose text content is not part of the document text.
+const HTML_RAW_TEXT_ELEMENTS: [&str; 2] = ["script", "style"];
+
+/// Elements whose tags separate the text before and after them.
+const HTML_SEPARATING_ELEMENTS: [&str; 39] = [
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "br",
+    "caption",
+    "dd",
+    "div",
+    "dl",
+    "dt",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "li",
+    "main",
+    "nav",
+    "ol",
+    "option",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "tbody",
+    "td",
+    "tfoot",
+    "th",
+    "thead",
+    "title",
+    "tr",
+    "ul",
+];
+
+/// Character references longer than this are not decoded.
+const MAX_HTML_ENTITY_LENGTH: usize = 32;
+
+/// Text being built by `strip_html`, with a space inserted where a separating
+/// tag was removed unless the text already has whitespace there.
+struct StrippedText {
+    text: String,
+    separate: bool,
+}
+
+impl StrippedText {
+    fn with_capacity(capacity: usize) -> StrippedText {
+        StrippedText {
+            text: String::with_capacity(capacity),
+            separate: false,
+        }
+    }
+
+    fn push_str(&mut self, text: &str) {
+        let first = match text.chars().next() {
+            Some(first) => first,
+            None => return,
+        };
+        let ends_with_whitespace = matches!(
+            self.text.chars().next_back(),
+            Some(last) if last.is_whitespace()
+        );
+        if self.separate && !self.text.is_empty() && !ends_with_whitespace && !first.is_whitespace()
+        {
+            self.text.push(' ');
+        }
+        self.separate = false;
+        self.text.push_str(text);
+    }
+}
+
+/// What `skip_html_markup` found at the start of its input.
+enum HtmlMarkup<'a> {
+    /// A tag or comment, followed by `rest`. `separates` is set for tags that
+    /// separate the text around them.
+    Skipped { rest: &'a str, separates: bool },
+    /// Text that does not start markup. No tag that starts within the first
+    /// `scanned` bytes, other than a comment, can end either.
+    NotMarkup { scanned: usize },
+}
+
+/// Skips the tag or comment at the start of `markup` (which starts with `<`).
+fn skip_html_markup(markup: &str) -> HtmlMarkup<'_> {
+    if let Some(comment) = markup.strip_prefix("<!--") {
+        let rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+        return HtmlMarkup::Skipped {
+            rest,
+            separates: false,
+        };
+    }
+
+    let after_bracket = &markup[1..];
+    let name = after_bracket.strip_prefix('/');
+    let is_closing = name.is_some();
+    let name = name.unwrap_or(after_bracket);
+    match name.chars().next() {
+        Some(first) if first.is_ascii_alphabetic() => {}
+        Some('!') | Some('?') if !is_closing => {}
+        _ => return HtmlMarkup::NotMarkup { scanned: 1 },
+    }
+
+    let tag_end = match find_html_tag_end(markup) {
+        Ok(tag_end) => tag_end,
+        Err(scanned) => return HtmlMarkup::NotMarkup { scanned },
+    };
+    let rest = &markup[tag_end..];
+    let name = &name[..name
+        .find(|character: char| !character.is_ascii_alphanumeric())
+        .unwrap_or(name.len())];
+    let separates = HTML_SEPARATING_ELEMENTS
+        .iter()
+        .any(|element| name.eq_ignore_ascii_case(element));
+    let raw_text_element = HTML_RAW_TEXT_ELEMENTS
+        .iter()
+        .find(|element| name.eq_ignore_ascii_case(element));
+
+    let rest = match raw_text_element {
+        Some(element) if !is_closing && !markup[..tag_end].ends_with("/>") => {
+            // Drop the element's content up to and including its closing tag.
+            // The search runs at most once per element and consumes all the
+            // text it looks at.
+            let mut search_from = 0;
+            let mut after_element = "";
+            while let Some(position) = rest[search_from..].find("</") {
+                let closing = &rest[search_from + position..];
+                let is_match = matches!(
+                    closing.get(2..2 + element.len()),
+                    Some(closing_name) if closing_name.eq_ignore_ascii_case(element)
+                );
+                if is_match {
+                    if let Ok(end) = find_html_tag_end(closing) {
+                        after_element = &closing[end..];
+                    }
+                    break;
+                }
+                search_from += position + 2;
+            }
+            after_element
+        }
+        _ => rest,
+    };
+    HtmlMarkup::Skipped { rest, separates }
+}
+
+/// Returns the index just past the `>` closing the tag at the start of
+/// `markup`, ignoring any `>` inside quoted attribute values.
+///
+/// A quoted value is given up on at the next `<` or newline, so that a stray
+/// quote does not swallow the markup after it. If no `>` is found, returns the
+/// index at which the scan stopped: a scan starting from any later `<` before
+/// that index would stop there too.
+fn find_html_tag_end(markup: &str) -> Result<usize, usize> {
+    let mut quote = None;
+    for (index, character) in markup.char_indices() {
+        match quote {
+            Some(open_quote) if character == open_quote => quote = None,
+            Some(_) if character == '<' || character == '\n' => return Err(index),
+            Some(_) => {}
+            None if character == '"' || character == '\'' => quote = Some(character),
+            None if character == '>' => return Ok(index + 1),
+            None => {}
+        }
+    }
+    Err(markup.len())
+}
+
+/// Decodes the character reference at the start of `entity` (which starts with
+/// `&`), returning the character and the length of the reference.
+fn decode_html_entity(entity: &str) -> Option<(char, usize)> {
+    let (end, _) = entity
+        .char_indices()
+        .take(MAX_HTML_ENTITY_LENGTH)
+        .find(|&(_, character)| character == ';')?;
+    let decoded = match &entity[1..end] {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => '\u{a0}',
+        name => {
+            let number = name.strip_prefix('#')?;
+            let code_point = match number.strip_prefix(|prefix| prefix == 'x' || prefix == 'X') {
+                Some(hex) if hex.chars().all(|digit| digit.is_ascii_hexdigit()) => {
+                    u32::from_str_radix(hex, 16).ok()?
+                }
+                None if number.chars().all(|digit| digit.is_ascii_digit()) => {
+                    number.parse().ok()?
+                }
+                _ => return None,
+            };
+            match code_point {
+                0 => std::char::REPLACEMENT_CHARACTER,
+                _ => std::char::from_u32(code_point)?,
+            }
+        }
+    };
+    Some((decoded, end + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        normalize_whitespace, strip_html, LowerCase, NormalizeWhitespace, Processor, StripHtml,
+    };
+    use std::borrow::Cow;
+
+    #[test]
+    fn normalize_whitespace_collapses_mixed_whitespace() {
+        assert_eq!(normalize_whitespace("hello  world\t!"), "hello world !");
+        assert_eq!(normalize_whitespace("a\r\n\r\nb \t c"), "a b c");
+    }
+
+    #[test]
+    fn normalize_whitespace_strips_leading_and_trailing() {
+        assert_eq!(normalize_whitespace("  hello world\n"), "hello world");
+        assert_eq!(normalize_whitespace(" \t\n"), "");
+    }
+
+    #[test]
+    fn normalize_whitespace_borrows_when_already_normalized() {
+        for source in &["", "hello", "hello world !"] {
+            let normalized = normalize_whitespace(source);
+            assert!(matches!(normalized, Cow::Borrowed(_)));
+            assert_eq!(&normalized, source);
+        }
+    }
+
+    #[test]
+    fn normalize_whitespace_allocates_when_needed() {
+        for source in &[" hello", "hello ", "hello  world", "hello\tworld"] {
+            assert!(matches!(normalize_whitespace(source), Cow::Owned(_)));
+        }
+    }
+
+    #[test]
+    fn lower_case_processor() {
+        assert_eq!(LowerCase.process("Hello WORLD"), "hello world");
+        assert_eq!(LowerCase.process("ÀÉÎ Σ"), "àéî σ");
+        assert!(matches!(
+            LowerCase.process("already lower 123"),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn normalize_whitespace_processor() {
+        assert_eq!(NormalizeWhitespace.process(" a \t b "), "a b");
+        assert!(matches!(
+            NormalizeWhitespace.process("a b"),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn strip_html_removes_tags() {
+        assert_eq!(strip_html("<b>hello</b> world"), "hello world");
+        assert_eq!(
+            strip_html("<div><p>Hello <em><b>big</b></em> world</p></div>"),
+            "Hello big world"
+        );
+        assert_eq!(strip_html("line<br/>break<!DOCTYPE html>"), "line break");
+    }
+
+    #[test]
+    fn strip_html_separates_block_elements() {
+        assert_eq!(strip_html("<p>Hello</p><p>World</p>"), "Hello World");
+        assert_eq!(
+            strip_html("<ul><li>one</li><LI>two</LI></ul>three<h2>four</h2>"),
+            "one two three four"
+        );
+        assert_eq!(strip_html("<td>a</td>\n<td>b</td>"), "a\nb");
+        assert_eq!(strip_html("a<br>b <hr/> c"), "a b  c");
+        assert_eq!(strip_html("<span>in</span><i>line</i>"), "inline");
+    }
+
+    #[test]
+    fn strip_html_removes_attributes() {
+        assert_eq!(
+            strip_html(r#"<a href="/x?a>b" title='it"s'>link</a> text"#),
+            "link text"
+        );
+    }
+
+    #[test]
+    fn strip_html_removes_script_and_style_content() {
+        assert_eq!(
+            strip_html(
+                "before<script type=\"text/javascript\">if (a < b) { x = '</div>'; }</script>after"
+            ),
+            "beforeafter"
+        );
+        assert_eq!(strip_html("<STYLE>p { color: red; }</Style>text"), "text");
+        assert_eq!(strip_html("a<script src=\"x.js\"/>b"), "ab");
+        assert_eq!(strip_html("a<script>never closed"), "a");
+    }
+
+    #[test]
+    fn strip_html_removes_comments() {
+        assert_eq!(strip_html("a<!-- <b>hidden</b> -->b"), "ab");
+        assert_eq!(strip_html("a<!-- unterminated"), "a");
+    }
+
+    #[test]
+    fn strip_html_decodes_entities() {
+        assert_eq!(
+            strip_html("Tom &amp; Jerry &lt;3 &quot;&apos; &#169; &#x41;&#X42;"),
+            "Tom & Jerry <3 \"' © AB"
+        );
+        assert_eq!(
+            strip_html("&unknown; & alone &#xZZ; &#; &#1114112;"),
+            "&unknown; & alone &#xZZ; &#; &#1114112;"
+        );
+        assert_eq!(strip_html("a&#0;b&#x0;"), "a\u{fffd}b\u{fffd}");
+    }
+
+    #[test]
+    fn strip_html_is_linear_on_unterminated_tags() {
+        // Each of these used to rescan the rest of the input for every `<`.
+        let inputs = [
+            "<a".repeat(100_000),
+            "<a href=\"".repeat(100_000),
+            format!("{}\"\n", "<a ".repeat(100_000)),
+            format!("{}&amp;", "<a ".repeat(100_000)),
+        ];
+        for source in &inputs {
+            let start = std::time::Instant::now();
+            let stripped = strip_html(source);
+            assert!(start.elapsed() < std::time::Duration::from_secs(5));
+            assert!(stripped.starts_with("<a"));
+        }
+        assert!(strip_html(&inputs[3]).ends_with("<a &"));
+    }
+
+    #[test]
+    fn strip_html_keeps_text_that_is_not_markup() {
+        assert_eq!(strip_html("1 < 2 and 3 > 2"), "1 < 2 and 3 > 2");
+        assert_eq!(strip_html("<b unterminated"), "<b unterminated");
+        assert_eq!(strip_html("<b x=\"<i>y</i>"), "<b x=\"y");
+        assert_eq!(
+            strip_html("<a title=\"unclosed\n<b>bold</b> &amp; more"),
+            "<a title=\"unclosed\nbold & more"
+        );
+        assert!(matches!(strip_html("no markup here"), Cow::Borrowed(_)));
+        assert_eq!(StripHtml.process("<i>x</i>"), "x");
+    }
+}
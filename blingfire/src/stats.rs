@@ -0,0 +1,246 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+};
+
+/// Returns the `k` most frequent token IDs in `freq_map` as `(id, count)`
+/// pairs, sorted by descending count.
+///
+/// Ties are broken by ascending token ID so the result is deterministic. If
+/// `k` is larger than the number of entries, all of them are returned. Runs in
+/// `O(n log k)` time.
+///
+/// ## Example
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// let freq_map: HashMap<i32, usize> = vec![(1, 10), (2, 30), (3, 20)].into_iter().collect();
+/// assert_eq!(blingfire::top_k_tokens(&freq_map, 2), vec![(2, 30), (3, 20)]);
+/// ```
+pub fn top_k_tokens(freq_map: &HashMap<i32, usize>, k: usize) -> Vec<(i32, usize)> {
+    if k == 0 {
+        return Vec::new();
+    }
+
+    // Min-heap of the best `k` entries seen so far, ordered by (count, Reverse(id)).
+    let mut heap = BinaryHeap::with_capacity(k.min(freq_map.len()) + 1);
+    for (&id, &count) in freq_map {
+        heap.push(Reverse((count, Reverse(id))));
+        if heap.len() > k {
+            heap.pop();
+        }
+    }
+
+    heap.into_sorted_vec()
+        .into_iter()
+        .map(|Reverse((count, Reverse(id)))| (id, count))
+        .collect()
+}
+
+/// Returns the number of occurrences of the unknown token `unk_id` in `ids`.
+#[inline]
+pub fn count_oov_tokens(ids: &[i32], unk_id: i32) -> usize {
+    ids.iter().filter(|&&id| id == unk_id).count()
+}
+
+/// Returns the fraction of `ids` that are the unknown token `unk_id`, or `0.0`
+/// if `ids` is empty.
+///
+/// ## Example
+///
+/// ```
+/// assert_eq!(blingfire::oov_rate(&[5, 3, 8, 3], 3), 0.5);
+/// ```
+pub fn oov_rate(ids: &[i32], unk_id: i32) -> f64 {
+    if ids.is_empty() {
+        return 0.0;
+    }
+    count_oov_tokens(ids, unk_id) as f64 / ids.len() as f64
+}
+
+/// Returns the fraction of all token IDs in `batch` that are the unknown token
+/// `unk_id`, or `0.0` if the batch contains no tokens.
+///
+/// Every token counts equally, so long sequences weigh more than short ones.
+pub fn batch_oov_rate(batch: &[Vec<i32>], unk_id: i32) -> f64 {
+    let (oov_count, total_count) = batch.iter().fold((0, 0), |(oov_count, total_count), ids| {
+        (
+            oov_count + count_oov_tokens(ids, unk_id),
+            total_count + ids.len(),
+        )
+    });
+    if total_count == 0 {
+        return 0.0;
+    }
+    oov_count as f64 / total_count as f64
+}
+
+/// Summary statistics of the sequence lengths in a batch, see `batch_statistics`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct BatchStats {
+    /// Length of the shortest sequence.
+    pub min_len: usize,
+    /// Length of the longest sequence.
+    pub max_len: usize,
+    /// Mean sequence length.
+    pub mean_len: f64,
+    /// Population standard deviation of the sequence lengths.
+    pub std_dev: f64,
+    /// Total number of tokens across all sequences.
+    pub total_tokens: usize,
+}
+
+/// Computes the distribution of sequence lengths in `batch` in a single pass.
+///
+/// All fields are zero for an empty batch.
+///
+/// ## Example
+///
+/// ```
+/// let stats = blingfire::batch_statistics(&[vec![1, 2], vec![3, 4, 5, 6]]);
+/// assert_eq!((stats.min_len, stats.max_len, stats.total_tokens), (2, 4, 6));
+/// assert_eq!((stats.mean_len, stats.std_dev), (3.0, 1.0));
+/// ```
+pub fn batch_statistics(batch: &[Vec<i32>]) -> BatchStats {
+    if batch.is_empty() {
+        return BatchStats::default();
+    }
+
+    let mut min_len = usize::MAX;
+    let mut max_len = 0;
+    let mut total_tokens = 0;
+    let mut sum_of_squares = 0.0;
+    for sequence in batch {
+        let len = sequence.len();
+        min_len = min_len.min(len);
+        max_len = max_len.max(len);
+        total_tokens += len;
+        sum_of_squares += (len as f64) * (len as f64);
+    }
+
+    let count = batch.len() as f64;
+    let mean_len = total_tokens as f64 / count;
+    // Clamp at zero in case rounding makes the variance slightly negative.
+    let variance = (sum_of_squares / count - mean_len * mean_len).max(0.0);
+    BatchStats {
+        min_len,
+        max_len,
+        mean_len,
+        std_dev: variance.sqrt(),
+        total_tokens,
+    }
+}
+
+/// Counts the sequences in `batch` falling into each length bucket.
+///
+/// `buckets` holds inclusive upper bounds in ascending order. Element `i` of the
+/// result counts sequences with `buckets[i - 1] < len <= buckets[i]`, and the
+/// extra last element counts sequences longer than every bound, so the result
+/// has `buckets.len() + 1` elements.
+///
+/// ## Example
+///
+/// ```
+/// let batch = vec![vec![1], vec![1, 2, 3], vec![1, 2, 3, 4, 5]];
+/// assert_eq!(blingfire::histogram(&batch, &[2, 4]), vec![1, 1, 1]);
+/// ```
+pub fn histogram(batch: &[Vec<i32>], buckets: &[usize]) -> Vec<usize> {
+    let mut counts = vec![0; buckets.len() + 1];
+    for sequence in batch {
+        counts[buckets.partition_point(|&bound| bound < sequence.len())] += 1;
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        batch_oov_rate, batch_statistics, count_oov_tokens, histogram, oov_rate, top_k_tokens,
+        BatchStats,
+    };
+    use std::collections::HashMap;
+
+    fn freq_map(entries: &[(i32, usize)]) -> HashMap<i32, usize> {
+        entries.iter().cloned().collect()
+    }
+
+    #[test]
+    fn top_k_tokens_returns_most_frequent_in_order() {
+        let freq_map = freq_map(&[(5, 1), (7, 40), (2, 12), (9, 3), (4, 25)]);
+        assert_eq!(top_k_tokens(&freq_map, 3), vec![(7, 40), (4, 25), (2, 12)]);
+    }
+
+    #[test]
+    fn top_k_tokens_breaks_ties_by_id() {
+        let freq_map = freq_map(&[(8, 2), (3, 2), (6, 2), (1, 5)]);
+        assert_eq!(top_k_tokens(&freq_map, 3), vec![(1, 5), (3, 2), (6, 2)]);
+    }
+
+    #[test]
+    fn top_k_tokens_k_larger_than_map() {
+        let freq_map = freq_map(&[(1, 1), (2, 2)]);
+        assert_eq!(top_k_tokens(&freq_map, 10), vec![(2, 2), (1, 1)]);
+        assert_eq!(top_k_tokens(&freq_map, 0), vec![]);
+        assert_eq!(top_k_tokens(&HashMap::new(), 3), vec![]);
+    }
+
+    #[test]
+    fn count_oov_tokens_counts_unk_ids() {
+        assert_eq!(count_oov_tokens(&[3, 1, 3, 3, 2], 3), 3);
+        assert_eq!(count_oov_tokens(&[1, 2], 3), 0);
+        assert_eq!(count_oov_tokens(&[], 3), 0);
+    }
+
+    #[test]
+    fn oov_rate_is_fraction_of_unk_ids() {
+        assert_eq!(oov_rate(&[3, 1, 3, 2], 3), 0.5);
+        assert_eq!(oov_rate(&[1, 2, 4, 5], 3), 0.0);
+        assert_eq!(oov_rate(&[], 3), 0.0);
+    }
+
+    #[test]
+    fn batch_oov_rate_weights_every_token_equally() {
+        // A per-sequence average would give (0 + 0 + 1) / 3 instead.
+        let batch = vec![vec![0, 0, 0, 0], vec![0, 0], vec![3, 3], vec![]];
+        assert_eq!(batch_oov_rate(&batch, 3), 0.25);
+        assert_eq!(batch_oov_rate(&[], 3), 0.0);
+        assert_eq!(batch_oov_rate(&[vec![]], 3), 0.0);
+    }
+
+    #[test]
+    fn batch_statistics_of_known_lengths() {
+        let batch: Vec<Vec<i32>> = [2, 4, 4, 4, 5, 5, 7, 9]
+            .iter()
+            .map(|&len| vec![0; len])
+            .collect();
+        assert_eq!(
+            batch_statistics(&batch),
+            BatchStats {
+                min_len: 2,
+                max_len: 9,
+                mean_len: 5.0,
+                std_dev: 2.0,
+                total_tokens: 40,
+            }
+        );
+    }
+
+    #[test]
+    fn batch_statistics_of_empty_batch() {
+        assert_eq!(batch_statistics(&[]), BatchStats::default());
+        let stats = batch_statistics(&[vec![], vec![]]);
+        assert_eq!((stats.min_len, stats.max_len, stats.mean_len), (0, 0, 0.0));
+    }
+
+    #[test]
+    fn histogram_counts_per_bucket() {
+        let batch: Vec<Vec<i32>> = [0, 1, 2, 3, 8, 16, 17, 100]
+            .iter()
+            .map(|&len| vec![0; len])
+            .collect();
+        assert_eq!(histogram(&batch, &[2, 8, 16]), vec![3, 2, 1, 2]);
+        assert_eq!(histogram(&batch, &[]), vec![8]);
+        assert_eq!(histogram(&[], &[4]), vec![0, 0]);
+    }
+}
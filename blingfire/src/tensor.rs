@@ -0,0 +1,54 @@
+/// Pads `batch` with `pad_id` into a `[batch.len(), max_seq_len]` `i64` tensor
+/// on the CPU, ready to be fed to a PyTorch model through `tch`.
+#[inline]
+pub fn batch_to_tensor(batch: &[Vec<i32>], pad_id: i32) -> tch::Tensor {
+    batch_to_tensor_on_device(batch, pad_id, tch::Device::Cpu)
+}
+
+/// Like `batch_to_tensor`, but places the tensor on `device`.
+pub fn batch_to_tensor_on_device(
+    batch: &[Vec<i32>],
+    pad_id: i32,
+    device: tch::Device,
+) -> tch::Tensor {
+    let max_seq_len = batch.iter().map(Vec::len).max().unwrap_or(0);
+    let mut padded = vec![i64::from(pad_id); batch.len() * max_seq_len];
+    for (row, sequence) in padded.chunks_mut(max_seq_len.max(1)).zip(batch) {
+        for (padded_id, &id) in row.iter_mut().zip(sequence) {
+            *padded_id = i64::from(id);
+        }
+    }
+    tch::Tensor::from_slice(&padded)
+        .view([batch.len() as i64, max_seq_len as i64])
+        .to_device(device)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::batch_to_tensor;
+
+    #[test]
+    fn batch_to_tensor_pads_to_longest_sequence() {
+        let batch = vec![vec![1, 2, 3], vec![4], vec![5, 6]];
+        let tensor = batch_to_tensor(&batch, -1);
+        assert_eq!(tensor.size(), vec![3, 3]);
+        assert_eq!(tensor.kind(), tch::Kind::Int64);
+        assert_eq!(tensor.device(), tch::Device::Cpu);
+
+        let expected = [[1, 2, 3], [4, -1, -1], [5, 6, -1]];
+        for (row, expected_row) in expected.iter().enumerate() {
+            for (column, &expected_id) in expected_row.iter().enumerate() {
+                assert_eq!(
+                    tensor.int64_value(&[row as i64, column as i64]),
+                    expected_id
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn batch_to_tensor_of_empty_batch() {
+        assert_eq!(batch_to_tensor(&[], 0).size(), vec![0, 0]);
+        assert_eq!(batch_to_tensor(&[vec![], vec![]], 0).size(), vec![2, 0]);
+    }
+}
@@ -0,0 +1,200 @@
+/// Which end of a sequence `batch_truncate` removes tokens from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TruncationSide {
+    /// Drop tokens from the start, keeping the last `max_len` tokens.
+    Left,
+    /// Drop tokens from the end, keeping the first `max_len` tokens.
+    Right,
+}
+
+/// Truncates every sequence in `batch` to at most `max_len` tokens.
+///
+/// Sequences that are already short enough are left untouched; no padding is
+/// added.
+///
+/// ## Example
+///
+/// ```
+/// use blingfire::TruncationSide;
+///
+/// let mut batch = vec![vec![1, 2, 3, 4], vec![5]];
+/// blingfire::batch_truncate(&mut batch, 2, TruncationSide::Left);
+/// assert_eq!(batch, vec![vec![3, 4], vec![5]]);
+/// ```
+pub fn batch_truncate(batch: &mut [Vec<i32>], max_len: usize, strategy: TruncationSide) {
+    for sequence in batch.iter_mut().filter(|sequence| sequence.len() > max_len) {
+        match strategy {
+            TruncationSide::Left => {
+                let excess = sequence.len() - max_len;
+                sequence.drain(..excess);
+            }
+            TruncationSide::Right => sequence.truncate(max_len),
+        }
+    }
+}
+
+/// Truncates `ids` to its first `head_tokens` and last `tail_tokens` tokens,
+/// joined by `separator_id`, to keep both the beginning and the end of a long
+/// sequence.
+///
+/// Sequences of at most `head_tokens + tail_tokens` tokens are left untouched,
+/// otherwise the result is `head_tokens + tail_tokens + 1` tokens long.
+///
+/// ## Example
+///
+/// ```
+/// let mut ids: Vec<i32> = (0..10).collect();
+/// blingfire::truncate_from_center(&mut ids, 2, 3, -1);
+/// assert_eq!(ids, vec![0, 1, -1, 7, 8, 9]);
+/// ```
+pub fn truncate_from_center(
+    ids: &mut Vec<i32>,
+    head_tokens: usize,
+    tail_tokens: usize,
+    separator_id: i32,
+) {
+    let kept_tokens = head_tokens.saturating_add(tail_tokens);
+    if ids.len() <= kept_tokens {
+        return;
+    }
+    let tail_start = ids.len() - tail_tokens;
+    ids.splice(head_tokens..tail_start, std::iter::once(separator_id));
+}
+
+/// Truncates `ids` to at most `max_len` tokens, preferring to end on one of
+/// `sentence_final_ids` (e.g. the IDs of `.` or an end-of-sequence token) so
+/// that the result does not stop mid-sentence.
+///
+/// After cutting `ids` to `max_len` tokens, the last `tolerance + 1` tokens are
+/// searched backwards for a sentence-final ID, and `ids` is cut just after the
+/// first one found, so at most `tolerance` more tokens are dropped. If there is
+/// none, `ids` keeps its first `max_len` tokens. Sequences of at most `max_len`
+/// tokens are left untouched.
+///
+/// ## Example
+///
+/// ```
+/// let period = 4;
+/// let mut ids = vec![1, 2, period, 5, 6, 7, period];
+/// blingfire::truncate_at_sentence_end(&mut ids, 5, &[period], 2);
+/// assert_eq!(ids, vec![1, 2, period]);
+/// ```
+pub fn truncate_at_sentence_end(
+    ids: &mut Vec<i32>,
+    max_len: usize,
+    sentence_final_ids: &[i32],
+    tolerance: usize,
+) {
+    if ids.len() <= max_len {
+        return;
+    }
+    ids.truncate(max_len);
+    let search_start = max_len.saturating_sub(tolerance.saturating_add(1));
+    if let Some(position) = ids[search_start..]
+        .iter()
+        .rposition(|id| sentence_final_ids.contains(id))
+    {
+        ids.truncate(search_start + position + 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{batch_truncate, truncate_at_sentence_end, truncate_from_center, TruncationSide};
+
+    #[test]
+    fn batch_truncate_right_keeps_first_tokens() {
+        let mut batch = vec![vec![1, 2, 3, 4, 5], vec![6, 7], vec![], vec![8, 9, 10]];
+        batch_truncate(&mut batch, 3, TruncationSide::Right);
+        assert_eq!(
+            batch,
+            vec![vec![1, 2, 3], vec![6, 7], vec![], vec![8, 9, 10]]
+        );
+    }
+
+    #[test]
+    fn batch_truncate_left_keeps_last_tokens() {
+        let mut batch = vec![vec![1, 2, 3, 4, 5], vec![6, 7], vec![], vec![8, 9, 10]];
+        batch_truncate(&mut batch, 3, TruncationSide::Left);
+        assert_eq!(
+            batch,
+            vec![vec![3, 4, 5], vec![6, 7], vec![], vec![8, 9, 10]]
+        );
+    }
+
+    #[test]
+    fn batch_truncate_to_zero() {
+        let mut batch = vec![vec![1, 2], vec![3]];
+        batch_truncate(&mut batch, 0, TruncationSide::Left);
+        assert_eq!(batch, vec![Vec::<i32>::new(), vec![]]);
+    }
+
+    #[test]
+    fn truncate_from_center_keeps_head_and_tail() {
+        let mut ids: Vec<i32> = (0..100).collect();
+        truncate_from_center(&mut ids, 5, 5, -1);
+        assert_eq!(ids.len(), 11);
+        assert_eq!(&ids[0..5], &[0, 1, 2, 3, 4]);
+        assert_eq!(ids[5], -1);
+        assert_eq!(&ids[6..11], &[95, 96, 97, 98, 99]);
+    }
+
+    #[test]
+    fn truncate_from_center_with_empty_head_or_tail() {
+        let mut ids = vec![1, 2, 3, 4];
+        truncate_from_center(&mut ids, 0, 2, 0);
+        assert_eq!(ids, vec![0, 3, 4]);
+
+        let mut ids = vec![1, 2, 3, 4];
+        truncate_from_center(&mut ids, 2, 0, 0);
+        assert_eq!(ids, vec![1, 2, 0]);
+    }
+
+    #[test]
+    fn truncate_from_center_leaves_short_sequences() {
+        let mut ids = vec![1, 2, 3, 4];
+        truncate_from_center(&mut ids, 2, 2, 0);
+        assert_eq!(ids, vec![1, 2, 3, 4]);
+        truncate_from_center(&mut ids, usize::MAX, 1, 0);
+        assert_eq!(ids, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn truncate_at_sentence_end_within_tolerance() {
+        let mut ids = vec![1, 2, 0, 3, 4, 5, 6, 0, 7];
+        truncate_at_sentence_end(&mut ids, 6, &[0, -1], 3);
+        assert_eq!(ids, vec![1, 2, 0]);
+
+        // The latest sentence end wins.
+        let mut ids = vec![0, 1, -1, 2, 3, 4];
+        truncate_at_sentence_end(&mut ids, 4, &[0, -1], 10);
+        assert_eq!(ids, vec![0, 1, -1]);
+
+        // A sentence end right at `max_len` drops nothing more.
+        let mut ids = vec![1, 2, 0, 3];
+        truncate_at_sentence_end(&mut ids, 3, &[0], 0);
+        assert_eq!(ids, vec![1, 2, 0]);
+    }
+
+    #[test]
+    fn truncate_at_sentence_end_outside_tolerance() {
+        let mut ids = vec![1, 0, 2, 3, 4, 5, 6];
+        truncate_at_sentence_end(&mut ids, 5, &[0], 2);
+        assert_eq!(ids, vec![1, 0, 2, 3, 4]);
+
+        let mut ids = vec![1, 2, 3, 4];
+        truncate_at_sentence_end(&mut ids, 3, &[], usize::MAX);
+        assert_eq!(ids, vec![1, 2, 3]);
+
+        let mut ids = vec![1, 2, 3];
+        truncate_at_sentence_end(&mut ids, 0, &[1], 5);
+        assert!(ids.is_empty());
+    }
+
+    #[test]
+    fn truncate_at_sentence_end_leaves_short_sequences() {
+        let mut ids = vec![1, 2, 3];
+        truncate_at_sentence_end(&mut ids, 3, &[2], 5);
+        assert_eq!(ids, vec![1, 2, 3]);
+    }
+}